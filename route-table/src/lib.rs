@@ -0,0 +1,1169 @@
+use regex::RegexSet;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteConfig {
+    // Lets a middleware hook scope itself to this route instead of running
+    // on every route in the table.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub backends: Vec<String>,
+    pub aggregation: AggregationStrategy,
+    pub condition: String,
+    // Regex patterns matched against the input payload, e.g. routing
+    // diffs matching `\.rs$` to a Rust reviewer purely from the content
+    // being reviewed rather than an external condition predicate.
+    #[serde(default)]
+    pub input_patterns: Vec<String>,
+    #[serde(default)]
+    pub input_match: InputMatchMode,
+    pub fail_mode: FailMode,
+    #[serde(deserialize_with = "deserialize_humantime_duration")]
+    pub timeout: Duration,
+    pub retries: u32,
+}
+
+// Whether all `input_patterns` must match the input, or just one of them.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub enum InputMatchMode {
+    #[default]
+    All,
+    Any,
+}
+
+// How a route's results are combined when it names more than one backend.
+#[derive(Debug, Clone, Deserialize)]
+pub enum AggregationStrategy {
+    // Try each backend in order; the first `Ok` wins. Equivalent to the
+    // single-backend behavior when `backends` has exactly one entry.
+    FirstSuccess,
+    // Invoke every backend and merge all successful `ReviewResult`s.
+    MergeAll,
+    // Invoke every backend; merge the results if at least `n` succeed and
+    // at least one does (`Quorum(0)` with no successes is still an error).
+    Quorum(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailMode {
+    Fallthrough,
+    HardFail,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Approved,
+    ChangesRequired,
+    DecisionNeeded,
+    Skipped,
+}
+
+pub struct ReviewResult {
+    pub verdict: Verdict,
+    pub findings: Vec<Finding>,
+    pub summary: String,
+}
+
+pub struct Finding {
+    pub severity: String,
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+// Condition expressions let a route say things like
+// `is_rust AND (touches_tests OR allow_untested) AND NOT is_draft`.
+//
+// Grammar:
+//   expr   := term (OR term)*
+//   term   := factor (AND factor)*
+//   factor := NOT factor | '(' expr ')' | IDENT
+#[derive(Debug, Clone, PartialEq)]
+enum ConditionToken {
+    Ident(String),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone)]
+enum ConditionExpr {
+    Ident(String),
+    And(Box<ConditionExpr>, Box<ConditionExpr>),
+    Or(Box<ConditionExpr>, Box<ConditionExpr>),
+    Not(Box<ConditionExpr>),
+}
+
+fn tokenize_condition(expr: &str) -> Result<Vec<ConditionToken>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            tokens.push(ConditionToken::LParen);
+            chars.next();
+        } else if c == ')' {
+            tokens.push(ConditionToken::RParen);
+            chars.next();
+        } else if c.is_alphanumeric() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    ident.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            match ident.as_str() {
+                "AND" => tokens.push(ConditionToken::And),
+                "OR" => tokens.push(ConditionToken::Or),
+                "NOT" => tokens.push(ConditionToken::Not),
+                _ => tokens.push(ConditionToken::Ident(ident)),
+            }
+        } else {
+            return Err(format!("unexpected character '{}' in condition", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct ConditionParser {
+    tokens: Vec<ConditionToken>,
+    pos: usize,
+}
+
+impl ConditionParser {
+    fn parse(expr: &str) -> Result<ConditionExpr, String> {
+        let tokens = tokenize_condition(expr)?;
+        let mut parser = ConditionParser { tokens, pos: 0 };
+        let expr = parser.parse_expr()?;
+        if parser.pos != parser.tokens.len() {
+            return Err("trailing tokens in condition".to_string());
+        }
+        Ok(expr)
+    }
+
+    fn peek(&self) -> Option<&ConditionToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_expr(&mut self) -> Result<ConditionExpr, String> {
+        let mut lhs = self.parse_term()?;
+        while self.peek() == Some(&ConditionToken::Or) {
+            self.pos += 1;
+            let rhs = self.parse_term()?;
+            lhs = ConditionExpr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_term(&mut self) -> Result<ConditionExpr, String> {
+        let mut lhs = self.parse_factor()?;
+        while self.peek() == Some(&ConditionToken::And) {
+            self.pos += 1;
+            let rhs = self.parse_factor()?;
+            lhs = ConditionExpr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_factor(&mut self) -> Result<ConditionExpr, String> {
+        match self.peek() {
+            Some(ConditionToken::Not) => {
+                self.pos += 1;
+                let inner = self.parse_factor()?;
+                Ok(ConditionExpr::Not(Box::new(inner)))
+            }
+            Some(ConditionToken::LParen) => {
+                self.pos += 1;
+                let inner = self.parse_expr()?;
+                match self.peek() {
+                    Some(ConditionToken::RParen) => {
+                        self.pos += 1;
+                        Ok(inner)
+                    }
+                    _ => Err("expected ')' in condition".to_string()),
+                }
+            }
+            Some(ConditionToken::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(ConditionExpr::Ident(name))
+            }
+            other => Err(format!("unexpected token in condition: {:?}", other)),
+        }
+    }
+}
+
+fn evaluate_condition(
+    expr: &ConditionExpr,
+    conditions: &HashMap<String, Box<dyn Fn() -> bool>>,
+) -> Result<bool, String> {
+    match expr {
+        ConditionExpr::Ident(name) => match conditions.get(name) {
+            Some(f) => Ok(f()),
+            None => Err(format!("unresolved condition: {}", name)),
+        },
+        ConditionExpr::And(lhs, rhs) => {
+            Ok(evaluate_condition(lhs, conditions)? && evaluate_condition(rhs, conditions)?)
+        }
+        ConditionExpr::Or(lhs, rhs) => {
+            Ok(evaluate_condition(lhs, conditions)? || evaluate_condition(rhs, conditions)?)
+        }
+        ConditionExpr::Not(inner) => Ok(!evaluate_condition(inner, conditions)?),
+    }
+}
+
+// Top-level shape of a route table config file (YAML or JSON). Backends and
+// condition predicates are still wired up in code via `register_backend`
+// and `register_condition`; only the routing policy itself is data-driven.
+#[derive(Debug, Deserialize)]
+pub struct RouteTableConfig {
+    pub routes: Vec<RouteConfig>,
+}
+
+fn deserialize_humantime_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw = String::deserialize(deserializer)?;
+    parse_humantime_duration(&raw).map_err(serde::de::Error::custom)
+}
+
+// Parses a small humantime-style subset: an integer followed by one of
+// `ms`, `s`, `m`, `h` (e.g. "500ms", "30s", "5m", "1h").
+fn parse_humantime_duration(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let split_at = raw
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("invalid duration: {}", raw))?;
+    let (value, unit) = raw.split_at(split_at);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("invalid duration: {}", raw))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(value)),
+        "s" => Ok(Duration::from_secs(value)),
+        "m" => Ok(Duration::from_secs(value * 60)),
+        "h" => Ok(Duration::from_secs(value * 3600)),
+        _ => Err(format!("unsupported duration unit '{}' in '{}'", unit, raw)),
+    }
+}
+
+// Merges the results of a fan-out route. Findings and summaries are
+// concatenated; the verdict with the highest precedence wins, so any
+// blocking finding from any backend wins overall.
+fn merge_results(results: Vec<ReviewResult>) -> ReviewResult {
+    let mut verdict = Verdict::Skipped;
+    let mut findings = Vec::new();
+    let mut summaries = Vec::new();
+
+    for result in results {
+        if verdict_precedence(&result.verdict) > verdict_precedence(&verdict) {
+            verdict = result.verdict;
+        }
+        findings.extend(result.findings);
+        summaries.push(result.summary);
+    }
+
+    ReviewResult {
+        verdict,
+        findings,
+        summary: summaries.join("\n"),
+    }
+}
+
+fn verdict_precedence(verdict: &Verdict) -> u8 {
+    match verdict {
+        Verdict::ChangesRequired => 3,
+        Verdict::DecisionNeeded => 2,
+        Verdict::Approved => 1,
+        Verdict::Skipped => 0,
+    }
+}
+
+type Backend = Arc<dyn Fn(&str) -> Result<ReviewResult, String> + Send + Sync>;
+
+// Lets a backend register itself just by being linked into the binary,
+// mirroring the collect-on-startup pattern used for pluggable actions.
+// A module declares itself with:
+//
+//   inventory::submit! {
+//       BackendRegistration { name: "security-scanner", factory: make_security_scanner }
+//   }
+type BoxedBackend = Box<dyn Fn(&str) -> Result<ReviewResult, String> + Send + Sync>;
+type BackendFactory = fn() -> BoxedBackend;
+
+pub struct BackendRegistration {
+    pub name: &'static str,
+    pub factory: BackendFactory,
+}
+
+inventory::collect!(BackendRegistration);
+
+#[cfg(test)]
+fn make_inventory_test_backend() -> BoxedBackend {
+    Box::new(|_: &str| {
+        Ok(ReviewResult {
+            verdict: Verdict::Approved,
+            findings: Vec::new(),
+            summary: "from-inventory".to_string(),
+        })
+    })
+}
+
+#[cfg(test)]
+inventory::submit! {
+    BackendRegistration {
+        name: "inventory-test-backend",
+        factory: make_inventory_test_backend,
+    }
+}
+
+type PreHook = (Option<String>, Box<dyn Fn(&str) -> Result<(), String>>);
+type PostHook = (Option<String>, Box<dyn Fn(&mut ReviewResult)>);
+
+pub struct RouteTable {
+    routes: Vec<RouteConfig>,
+    // Compiled once per route, in lockstep with `routes`, so `execute`
+    // doesn't recompile patterns on every call.
+    input_matchers: Vec<RegexSet>,
+    backends: HashMap<String, Backend>,
+    conditions: HashMap<String, Box<dyn Fn() -> bool>>,
+    pre_hooks: Vec<PreHook>,
+    post_hooks: Vec<PostHook>,
+}
+
+impl Default for RouteTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouteTable {
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            input_matchers: Vec::new(),
+            backends: HashMap::new(),
+            conditions: HashMap::new(),
+            pre_hooks: Vec::new(),
+            post_hooks: Vec::new(),
+        }
+    }
+
+    pub fn add_route(&mut self, config: RouteConfig) {
+        let matcher = RegexSet::new(&config.input_patterns).unwrap_or_else(|e| {
+            eprintln!("invalid input pattern in route: {}", e);
+            RegexSet::empty()
+        });
+        self.input_matchers.push(matcher);
+        self.routes.push(config);
+    }
+
+    // Runs before a matched route's backend(s) are invoked. An `Err` is
+    // treated like a backend failure and respects the route's `FailMode`.
+    pub fn register_pre_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        self.pre_hooks.push((None, Box::new(hook)));
+    }
+
+    // Same as `register_pre_hook`, but only runs for routes whose `name`
+    // matches `route_name`.
+    pub fn register_pre_hook_for<F>(&mut self, route_name: &str, hook: F)
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        self.pre_hooks.push((Some(route_name.to_string()), Box::new(hook)));
+    }
+
+    // Runs on every successful `ReviewResult` before it's returned.
+    pub fn register_post_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&mut ReviewResult) + 'static,
+    {
+        self.post_hooks.push((None, Box::new(hook)));
+    }
+
+    // Same as `register_post_hook`, but only runs for routes whose `name`
+    // matches `route_name`.
+    pub fn register_post_hook_for<F>(&mut self, route_name: &str, hook: F)
+    where
+        F: Fn(&mut ReviewResult) + 'static,
+    {
+        self.post_hooks.push((Some(route_name.to_string()), Box::new(hook)));
+    }
+
+    pub fn from_yaml(path: &str) -> Result<Self, String> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+        let config: RouteTableConfig = serde_yaml::from_str(&contents)
+            .map_err(|e| format!("failed to parse route table yaml: {}", e))?;
+        Ok(Self::from_config(config))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: RouteTableConfig = serde_json::from_str(json)
+            .map_err(|e| format!("failed to parse route table json: {}", e))?;
+        Ok(Self::from_config(config))
+    }
+
+    fn from_config(config: RouteTableConfig) -> Self {
+        let mut table = Self::new();
+        for route in config.routes {
+            table.add_route(route);
+        }
+        table
+    }
+
+    // Populates `backends` from every `BackendRegistration` linked into the
+    // binary, so new review engines just need `inventory::submit!` instead
+    // of a manual `register_backend` call at startup.
+    pub fn with_registered_backends() -> Self {
+        let mut table = Self::new();
+        for registration in inventory::iter::<BackendRegistration> {
+            let handler = (registration.factory)();
+            table.backends.insert(registration.name.to_string(), Arc::from(handler));
+        }
+        table
+    }
+
+    // `Send + Sync` is required so a handler can be invoked from the worker
+    // thread `execute` spawns to enforce `RouteConfig::timeout`.
+    pub fn register_backend<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(&str) -> Result<ReviewResult, String> + Send + Sync + 'static,
+    {
+        self.backends.insert(name.to_string(), Arc::new(handler));
+    }
+
+    // Registers a predicate resolvable as `name` in a route's condition
+    // expression (see `ConditionParser`).
+    pub fn register_condition<F>(&mut self, name: &str, predicate: F)
+    where
+        F: Fn() -> bool + 'static,
+    {
+        self.conditions.insert(name.to_string(), Box::new(predicate));
+    }
+
+    pub fn execute(&self, input: &str) -> Result<ReviewResult, String> {
+        for (index, route) in self.routes.iter().enumerate() {
+            if !self.input_matches(index, route, input) {
+                continue;
+            }
+
+            let conditions_met = match ConditionParser::parse(&route.condition)
+                .and_then(|expr| evaluate_condition(&expr, &self.conditions))
+            {
+                Ok(met) => met,
+                Err(e) => {
+                    if route.fail_mode == FailMode::HardFail {
+                        return Err(e);
+                    }
+                    false
+                }
+            };
+
+            if !conditions_met {
+                continue;
+            }
+
+            if let Err(e) = self.run_pre_hooks(route, input) {
+                if route.fail_mode == FailMode::HardFail {
+                    return Err(e);
+                }
+                continue;
+            }
+
+            match self.execute_route(route, input) {
+                Ok(mut result) => {
+                    self.run_post_hooks(route, &mut result);
+                    return Ok(result);
+                }
+                Err(e) => {
+                    if route.fail_mode == FailMode::HardFail {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        Err("All routes exhausted".to_string())
+    }
+
+    // A route with no `input_patterns` is eligible regardless of content.
+    fn input_matches(&self, index: usize, route: &RouteConfig, input: &str) -> bool {
+        if route.input_patterns.is_empty() {
+            return true;
+        }
+
+        let matches = self.input_matchers[index].matches(input);
+        match route.input_match {
+            InputMatchMode::All => matches.iter().count() == route.input_patterns.len(),
+            InputMatchMode::Any => matches.matched_any(),
+        }
+    }
+
+    fn run_pre_hooks(&self, route: &RouteConfig, input: &str) -> Result<(), String> {
+        for (scope, hook) in &self.pre_hooks {
+            if scope.is_none() || *scope == route.name {
+                hook(input)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn run_post_hooks(&self, route: &RouteConfig, result: &mut ReviewResult) {
+        for (scope, hook) in &self.post_hooks {
+            if scope.is_none() || *scope == route.name {
+                hook(result);
+            }
+        }
+    }
+
+    fn execute_route(&self, route: &RouteConfig, input: &str) -> Result<ReviewResult, String> {
+        match &route.aggregation {
+            AggregationStrategy::FirstSuccess => {
+                let mut last_err = format!("no backends configured for route: {:?}", route.backends);
+                for name in &route.backends {
+                    match self.invoke_backend(name, input, route) {
+                        Ok(result) => return Ok(result),
+                        Err(e) => last_err = e,
+                    }
+                }
+                Err(last_err)
+            }
+            AggregationStrategy::MergeAll => {
+                let results: Vec<ReviewResult> = route
+                    .backends
+                    .iter()
+                    .filter_map(|name| self.invoke_backend(name, input, route).ok())
+                    .collect();
+                if results.is_empty() {
+                    return Err(format!("no backend in route succeeded: {:?}", route.backends));
+                }
+                Ok(merge_results(results))
+            }
+            AggregationStrategy::Quorum(n) => {
+                let results: Vec<ReviewResult> = route
+                    .backends
+                    .iter()
+                    .filter_map(|name| self.invoke_backend(name, input, route).ok())
+                    .collect();
+                // A quorum of zero would trivially "succeed" with nothing to
+                // merge, masking a misconfigured or empty `backends` list.
+                if results.is_empty() || results.len() < *n {
+                    return Err(format!(
+                        "quorum not met: {} of {} required backends succeeded",
+                        results.len(),
+                        n
+                    ));
+                }
+                Ok(merge_results(results))
+            }
+        }
+    }
+
+    // Runs a single named backend through its retry loop, honoring
+    // `RouteConfig::timeout` on every attempt.
+    fn invoke_backend(
+        &self,
+        name: &str,
+        input: &str,
+        route: &RouteConfig,
+    ) -> Result<ReviewResult, String> {
+        let handler = match self.backends.get(name) {
+            Some(h) => h.clone(),
+            None => return Err(format!("Unknown backend: {}", name)),
+        };
+
+        let mut last_err = format!("backend {} made no attempts", name);
+        for attempt in 0..=route.retries {
+            match Self::run_with_timeout(&handler, input, route.timeout) {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    eprintln!("Backend {} attempt {} failed: {}", name, attempt + 1, e);
+                    last_err = e;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    // Runs `handler` on a worker thread so a hung backend can't block the
+    // rest of the route table past its declared `timeout`. The thread is
+    // left to finish (or hang) on its own; its result is discarded if the
+    // channel has already timed out.
+    //
+    // Known leak: the spawned thread is never joined or cancelled on
+    // timeout. A backend that hangs permanently leaks one OS thread per
+    // attempt, with no pool or cap, for as long as the process runs.
+    fn run_with_timeout(
+        handler: &Backend,
+        input: &str,
+        timeout: Duration,
+    ) -> Result<ReviewResult, String> {
+        let handler = handler.clone();
+        let input = input.to_string();
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let _ = tx.send(handler(&input));
+        });
+
+        match rx.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err("timeout".to_string()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err("backend thread panicked before completing".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod timeout_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn approved() -> ReviewResult {
+        ReviewResult {
+            verdict: Verdict::Approved,
+            findings: Vec::new(),
+            summary: String::new(),
+        }
+    }
+
+    fn slow_route(timeout: Duration, retries: u32) -> RouteConfig {
+        RouteConfig {
+            name: None,
+            backends: vec!["slow".to_string()],
+            aggregation: AggregationStrategy::FirstSuccess,
+            condition: "always".to_string(),
+            input_patterns: Vec::new(),
+            input_match: InputMatchMode::All,
+            fail_mode: FailMode::HardFail,
+            timeout,
+            retries,
+        }
+    }
+
+    #[test]
+    fn slow_backend_times_out_as_a_failure() {
+        let mut table = RouteTable::new();
+        table.conditions.insert("always".to_string(), Box::new(|| true));
+        table.register_backend("slow", |_| {
+            thread::sleep(Duration::from_millis(100));
+            Ok(approved())
+        });
+        table.add_route(slow_route(Duration::from_millis(20), 0));
+
+        assert!(table.execute("x").is_err());
+    }
+
+    #[test]
+    fn panicking_backend_is_reported_distinctly_from_a_timeout() {
+        let mut table = RouteTable::new();
+        table.conditions.insert("always".to_string(), Box::new(|| true));
+        table.register_backend("slow", |_| panic!("boom"));
+        table.add_route(slow_route(Duration::from_secs(5), 0));
+
+        match table.execute("x") {
+            Err(e) => assert!(e.contains("panicked"), "unexpected error: {e}"),
+            Ok(_) => panic!("a panicking backend can't produce a result"),
+        }
+    }
+
+    #[test]
+    fn retries_after_timeout_can_still_succeed() {
+        let mut table = RouteTable::new();
+        table.conditions.insert("always".to_string(), Box::new(|| true));
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts_clone = attempts.clone();
+        table.register_backend("slow", move |_| {
+            if attempts_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                thread::sleep(Duration::from_millis(100));
+                Ok(approved()) // would have succeeded, but too late for this attempt
+            } else {
+                Ok(approved()) // the retry is fast and meets the timeout
+            }
+        });
+        table.add_route(slow_route(Duration::from_millis(20), 1));
+
+        assert!(table.execute("x").is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    // Documents the known leak noted on `run_with_timeout`: a worker thread
+    // that finishes after its `recv_timeout` has already elapsed is never
+    // joined or cancelled, so its result is simply discarded.
+    #[test]
+    fn late_completion_after_timeout_is_discarded_not_joined() {
+        let mut table = RouteTable::new();
+        table.conditions.insert("always".to_string(), Box::new(|| true));
+
+        let completed = Arc::new(AtomicUsize::new(0));
+        let completed_clone = completed.clone();
+        table.register_backend("slow", move |_| {
+            thread::sleep(Duration::from_millis(100));
+            completed_clone.fetch_add(1, Ordering::SeqCst);
+            Ok(approved())
+        });
+        table.add_route(slow_route(Duration::from_millis(20), 0));
+
+        assert!(table.execute("x").is_err());
+        assert_eq!(completed.load(Ordering::SeqCst), 0);
+
+        thread::sleep(Duration::from_millis(150));
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[cfg(test)]
+mod middleware_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn approved() -> ReviewResult {
+        ReviewResult {
+            verdict: Verdict::Approved,
+            findings: Vec::new(),
+            summary: String::new(),
+        }
+    }
+
+    fn route(name: Option<&str>) -> RouteConfig {
+        RouteConfig {
+            name: name.map(|n| n.to_string()),
+            backends: vec!["noop".to_string()],
+            aggregation: AggregationStrategy::FirstSuccess,
+            condition: "always".to_string(),
+            input_patterns: Vec::new(),
+            input_match: InputMatchMode::All,
+            fail_mode: FailMode::HardFail,
+            timeout: Duration::from_secs(1),
+            retries: 0,
+        }
+    }
+
+    fn table_with_always_true() -> RouteTable {
+        let mut table = RouteTable::new();
+        table.register_condition("always", || true);
+        table.register_backend("noop", |_| Ok(approved()));
+        table
+    }
+
+    #[test]
+    fn global_pre_hook_runs_before_the_backend() {
+        let mut table = table_with_always_true();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        table.register_pre_hook(move |_| {
+            calls_clone.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        table.add_route(route(None));
+
+        assert!(table.execute("x").is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn pre_hook_error_hard_fails_the_route() {
+        let mut table = table_with_always_true();
+        table.register_pre_hook(|_| Err("blocked".to_string()));
+        table.add_route(route(None));
+
+        assert!(table.execute("x").is_err());
+    }
+
+    #[test]
+    fn pre_hook_error_on_fallthrough_route_is_skipped() {
+        let mut table = table_with_always_true();
+        table.register_pre_hook(|_| Err("blocked".to_string()));
+        let mut r = route(None);
+        r.fail_mode = FailMode::Fallthrough;
+        table.add_route(r);
+
+        match table.execute("x") {
+            Err(e) => assert_eq!(e, "All routes exhausted"),
+            Ok(_) => panic!("expected the route to be skipped"),
+        }
+    }
+
+    #[test]
+    fn post_hook_mutates_the_result() {
+        let mut table = table_with_always_true();
+        table.register_post_hook(|result| result.summary = "rewritten".to_string());
+        table.add_route(route(None));
+
+        let reviewed = table.execute("x").expect("route should succeed");
+        assert_eq!(reviewed.summary, "rewritten");
+    }
+
+    #[test]
+    fn hooks_scoped_to_a_route_name_do_not_run_for_other_routes() {
+        let mut table = table_with_always_true();
+        table.register_post_hook_for("special", |result| result.summary = "scoped".to_string());
+        table.add_route(route(Some("ordinary")));
+
+        let reviewed = table.execute("x").expect("route should succeed");
+        assert_eq!(reviewed.summary, "");
+    }
+
+    #[test]
+    fn hooks_scoped_to_a_route_name_run_for_a_matching_route() {
+        let mut table = table_with_always_true();
+        table.register_post_hook_for("special", |result| result.summary = "scoped".to_string());
+        table.add_route(route(Some("special")));
+
+        let reviewed = table.execute("x").expect("route should succeed");
+        assert_eq!(reviewed.summary, "scoped");
+    }
+}
+
+#[cfg(test)]
+mod input_matching_tests {
+    use super::*;
+
+    fn approved() -> ReviewResult {
+        ReviewResult {
+            verdict: Verdict::Approved,
+            findings: Vec::new(),
+            summary: String::new(),
+        }
+    }
+
+    fn route(patterns: &[&str], input_match: InputMatchMode) -> RouteConfig {
+        RouteConfig {
+            name: None,
+            backends: vec!["noop".to_string()],
+            aggregation: AggregationStrategy::FirstSuccess,
+            condition: "always".to_string(),
+            input_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            input_match,
+            fail_mode: FailMode::Fallthrough,
+            timeout: Duration::from_secs(1),
+            retries: 0,
+        }
+    }
+
+    fn table_with_always_true() -> RouteTable {
+        let mut table = RouteTable::new();
+        table.register_condition("always", || true);
+        table.register_backend("noop", |_| Ok(approved()));
+        table
+    }
+
+    #[test]
+    fn route_with_no_patterns_matches_any_input() {
+        let mut table = table_with_always_true();
+        table.add_route(route(&[], InputMatchMode::All));
+
+        assert!(table.execute("anything at all").is_ok());
+    }
+
+    #[test]
+    fn all_mode_requires_every_pattern_to_match() {
+        let mut table = table_with_always_true();
+        table.add_route(route(&[r"\.rs$", "fn "], InputMatchMode::All));
+
+        assert!(table.execute("fn main() {}\nsrc/main.rs").is_ok());
+        match table.execute("src/main.rs") {
+            Err(e) => assert_eq!(e, "All routes exhausted"),
+            Ok(_) => panic!("only one of the two patterns matched"),
+        }
+    }
+
+    #[test]
+    fn any_mode_requires_only_one_pattern_to_match() {
+        let mut table = table_with_always_true();
+        table.add_route(route(&[r"\.rs$", r"\.sql$"], InputMatchMode::Any));
+
+        assert!(table.execute("src/main.rs").is_ok());
+        assert!(table.execute("migrations/001.sql").is_ok());
+        match table.execute("README.md") {
+            Err(e) => assert_eq!(e, "All routes exhausted"),
+            Ok(_) => panic!("neither pattern should match"),
+        }
+    }
+
+    #[test]
+    fn invalid_pattern_falls_back_to_never_matching() {
+        let mut table = table_with_always_true();
+        // A leading `*` has nothing to repeat and is invalid regex.
+        table.add_route(route(&["*.rs"], InputMatchMode::Any));
+
+        match table.execute("src/main.rs") {
+            Err(e) => assert_eq!(e, "All routes exhausted"),
+            Ok(_) => panic!("an invalid pattern should never match"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod inventory_tests {
+    use super::*;
+
+    #[test]
+    fn with_registered_backends_picks_up_inventory_submissions() {
+        let mut table = RouteTable::with_registered_backends();
+        assert!(table.backends.contains_key("inventory-test-backend"));
+
+        table.register_condition("always", || true);
+        table.add_route(RouteConfig {
+            name: None,
+            backends: vec!["inventory-test-backend".to_string()],
+            aggregation: AggregationStrategy::FirstSuccess,
+            condition: "always".to_string(),
+            input_patterns: Vec::new(),
+            input_match: InputMatchMode::All,
+            fail_mode: FailMode::HardFail,
+            timeout: Duration::from_secs(1),
+            retries: 0,
+        });
+
+        let reviewed = table
+            .execute("x")
+            .expect("the auto-registered backend should handle the route");
+        assert_eq!(reviewed.summary, "from-inventory");
+    }
+}
+
+#[cfg(test)]
+mod aggregation_tests {
+    use super::*;
+
+    fn result(verdict: Verdict, summary: &str) -> ReviewResult {
+        ReviewResult {
+            verdict,
+            findings: vec![Finding {
+                severity: "info".to_string(),
+                file: "a.rs".to_string(),
+                line: 1,
+                message: summary.to_string(),
+            }],
+            summary: summary.to_string(),
+        }
+    }
+
+    fn route(backends: &[&str], aggregation: AggregationStrategy) -> RouteConfig {
+        RouteConfig {
+            name: None,
+            backends: backends.iter().map(|s| s.to_string()).collect(),
+            aggregation,
+            condition: "always".to_string(),
+            input_patterns: Vec::new(),
+            input_match: InputMatchMode::All,
+            fail_mode: FailMode::HardFail,
+            timeout: Duration::from_secs(1),
+            retries: 0,
+        }
+    }
+
+    fn table_with_always_true() -> RouteTable {
+        let mut table = RouteTable::new();
+        table.register_condition("always", || true);
+        table
+    }
+
+    #[test]
+    fn first_success_tries_backends_in_order() {
+        let mut table = table_with_always_true();
+        table.register_backend("broken", |_| Err("down".to_string()));
+        table.register_backend("ok", |_| Ok(result(Verdict::Approved, "ok")));
+        table.add_route(route(&["broken", "ok"], AggregationStrategy::FirstSuccess));
+
+        let reviewed = table.execute("x").expect("a later backend should succeed");
+        assert_eq!(reviewed.summary, "ok");
+    }
+
+    #[test]
+    fn merge_all_concatenates_findings_and_takes_highest_precedence_verdict() {
+        let mut table = table_with_always_true();
+        table.register_backend("linter", |_| Ok(result(Verdict::Approved, "linter ok")));
+        table.register_backend("security", |_| {
+            Ok(result(Verdict::ChangesRequired, "security flagged an issue"))
+        });
+        table.add_route(route(
+            &["linter", "security"],
+            AggregationStrategy::MergeAll,
+        ));
+
+        let reviewed = table.execute("x").expect("both backends succeed");
+        assert_eq!(reviewed.verdict, Verdict::ChangesRequired);
+        assert_eq!(reviewed.findings.len(), 2);
+        assert!(reviewed.summary.contains("linter ok"));
+        assert!(reviewed.summary.contains("security flagged an issue"));
+    }
+
+    #[test]
+    fn merge_all_fails_when_every_backend_fails() {
+        let mut table = table_with_always_true();
+        table.register_backend("broken", |_| Err("down".to_string()));
+        table.add_route(route(&["broken"], AggregationStrategy::MergeAll));
+
+        assert!(table.execute("x").is_err());
+    }
+
+    #[test]
+    fn quorum_succeeds_once_enough_backends_agree() {
+        let mut table = table_with_always_true();
+        table.register_backend("a", |_| Ok(result(Verdict::Approved, "a")));
+        table.register_backend("b", |_| Ok(result(Verdict::Approved, "b")));
+        table.register_backend("c", |_| Err("down".to_string()));
+        table.add_route(route(&["a", "b", "c"], AggregationStrategy::Quorum(2)));
+
+        let reviewed = table.execute("x").expect("2 of 3 backends succeeded");
+        assert_eq!(reviewed.findings.len(), 2);
+    }
+
+    #[test]
+    fn quorum_fails_when_not_enough_backends_succeed() {
+        let mut table = table_with_always_true();
+        table.register_backend("a", |_| Ok(result(Verdict::Approved, "a")));
+        table.register_backend("b", |_| Err("down".to_string()));
+        table.add_route(route(&["a", "b"], AggregationStrategy::Quorum(2)));
+
+        assert!(table.execute("x").is_err());
+    }
+
+    #[test]
+    fn quorum_of_zero_with_no_backends_is_rejected_not_a_silent_success() {
+        let mut table = table_with_always_true();
+        table.add_route(route(&[], AggregationStrategy::Quorum(0)));
+
+        assert!(table.execute("x").is_err());
+    }
+}
+
+#[cfg(test)]
+mod condition_tests {
+    use super::*;
+
+    fn conditions(values: &[(&str, bool)]) -> HashMap<String, Box<dyn Fn() -> bool>> {
+        values
+            .iter()
+            .map(|(name, value)| {
+                let value = *value;
+                (name.to_string(), Box::new(move || value) as Box<dyn Fn() -> bool>)
+            })
+            .collect()
+    }
+
+    fn eval(expr: &str, conditions: &HashMap<String, Box<dyn Fn() -> bool>>) -> Result<bool, String> {
+        ConditionParser::parse(expr).and_then(|expr| evaluate_condition(&expr, conditions))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // `false OR true AND false` must parse as `false OR (true AND false)` == false,
+        // not `(false OR true) AND false` == false either way here, so also check a
+        // case where the two groupings disagree.
+        let c = conditions(&[("a", true), ("b", false), ("c", true)]);
+        assert_eq!(eval("a OR b AND c", &c), Ok(true)); // a OR (b AND c) == true OR false
+        assert_eq!(eval("b AND c OR a", &c), Ok(true)); // (b AND c) OR a == false OR true
+        assert_eq!(eval("b AND (c OR a)", &c), Ok(false)); // explicit grouping flips it
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        // Without the parentheses, `a AND b OR c` is `(a AND b) OR c` == true.
+        let c = conditions(&[("a", false), ("b", false), ("c", true)]);
+        assert_eq!(eval("a AND (b OR c)", &c), Ok(false));
+        assert_eq!(eval("(a AND b) OR c", &c), Ok(true));
+    }
+
+    #[test]
+    fn not_negates_its_operand() {
+        let c = conditions(&[("a", true), ("b", false)]);
+        assert_eq!(eval("NOT a", &c), Ok(false));
+        assert_eq!(eval("NOT a AND NOT b", &c), Ok(false));
+        assert_eq!(eval("NOT (a AND b)", &c), Ok(true));
+    }
+
+    #[test]
+    fn unresolved_identifier_is_an_error() {
+        let c = conditions(&[("a", true)]);
+        assert!(eval("a AND missing", &c).is_err());
+    }
+
+    #[test]
+    fn hard_fail_route_surfaces_unresolved_condition_error() {
+        let mut table = RouteTable::new();
+        table.register_backend("noop", |_| {
+            Ok(ReviewResult {
+                verdict: Verdict::Approved,
+                findings: Vec::new(),
+                summary: String::new(),
+            })
+        });
+        table.add_route(test_route("missing", FailMode::HardFail));
+
+        assert!(table.execute("anything").is_err());
+    }
+
+    #[test]
+    fn fallthrough_route_skips_unresolved_condition() {
+        let mut table = RouteTable::new();
+        table.register_backend("noop", |_| {
+            Ok(ReviewResult {
+                verdict: Verdict::Approved,
+                findings: Vec::new(),
+                summary: String::new(),
+            })
+        });
+        table.add_route(test_route("missing", FailMode::Fallthrough));
+
+        match table.execute("anything") {
+            Err(e) => assert_eq!(e, "All routes exhausted"),
+            Ok(_) => panic!("expected the route to be skipped"),
+        }
+    }
+
+    #[test]
+    fn trailing_tokens_are_rejected() {
+        assert!(ConditionParser::parse("a b").is_err());
+    }
+
+    #[test]
+    fn unmatched_parens_are_rejected() {
+        assert!(ConditionParser::parse("(a AND b").is_err());
+        assert!(ConditionParser::parse("a AND b)").is_err());
+    }
+
+    #[test]
+    fn empty_condition_is_rejected() {
+        assert!(ConditionParser::parse("").is_err());
+    }
+
+    fn test_route(condition: &str, fail_mode: FailMode) -> RouteConfig {
+        RouteConfig {
+            name: None,
+            backends: vec!["noop".to_string()],
+            aggregation: AggregationStrategy::FirstSuccess,
+            condition: condition.to_string(),
+            input_patterns: Vec::new(),
+            input_match: InputMatchMode::All,
+            fail_mode,
+            timeout: Duration::from_secs(1),
+            retries: 0,
+        }
+    }
+}